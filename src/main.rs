@@ -1,12 +1,16 @@
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::cmp::{Ordering, PartialEq};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use structopt::StructOpt;
 
-#[derive(Debug, Deserialize, Eq, Ord, PartialEq, Serialize)]
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 struct Record {
     #[serde(with = "chrono::serde::ts_seconds")]
     timestamp: chrono::DateTime<chrono::Utc>,
@@ -15,24 +19,166 @@ struct Record {
     pulse: u32,
 }
 
-impl Display for Record {
+/// AHA blood pressure categories, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Category {
+    Normal,
+    Elevated,
+    Stage1,
+    Stage2,
+    Crisis,
+}
+
+impl Category {
+    const ALL: [Category; 5] = [
+        Category::Normal,
+        Category::Elevated,
+        Category::Stage1,
+        Category::Stage2,
+        Category::Crisis,
+    ];
+
+    fn classify(systolic: u32, diastolic: u32) -> Category {
+        if systolic > 180 || diastolic > 120 {
+            Category::Crisis
+        } else if systolic >= 140 || diastolic >= 90 {
+            Category::Stage2
+        } else if systolic >= 130 || diastolic >= 80 {
+            Category::Stage1
+        } else if systolic >= 120 {
+            Category::Elevated
+        } else {
+            Category::Normal
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Category::Normal => "Normal",
+            Category::Elevated => "Elevated",
+            Category::Stage1 => "Stage 1 Hypertension",
+            Category::Stage2 => "Stage 2 Hypertension",
+            Category::Crisis => "Hypertensive Crisis",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+impl FromStr for Category {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(Category::Normal),
+            "elevated" => Ok(Category::Elevated),
+            "stage1" | "stage-1" => Ok(Category::Stage1),
+            "stage2" | "stage-2" => Ok(Category::Stage2),
+            "crisis" => Ok(Category::Crisis),
+            other => bail!(
+                "Unknown category '{}', expected normal, elevated, stage1, stage2, or crisis",
+                other
+            ),
+        }
+    }
+}
+
+impl PartialOrd for Record {
+    fn partial_cmp(&self, other: &Record) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Record {
+    fn cmp(&self, other: &Record) -> Ordering {
+        self.timestamp.cmp(&other.timestamp)
+    }
+}
+
+struct RecordDisplay<'a> {
+    record: &'a Record,
+    timezone: &'a TimeZoneSetting,
+}
+
+impl Display for RecordDisplay<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let local_timestamp: chrono::DateTime<chrono::Local> =
-            chrono::DateTime::from(self.timestamp);
         write!(
             f,
-            "{}\tBP: {}/{}\tPulse: {}",
-            local_timestamp.format("%Y-%m-%d %I:%M%P"),
-            self.systolic,
-            self.diastolic,
-            self.pulse
+            "{}\tBP: {}/{}\tPulse: {}\t{}",
+            self.timezone
+                .format(self.record.timestamp, "%Y-%m-%d %I:%M%P"),
+            self.record.systolic,
+            self.record.diastolic,
+            self.record.pulse,
+            Category::classify(self.record.systolic, self.record.diastolic)
         )
     }
 }
 
-impl PartialOrd for Record {
-    fn partial_cmp(&self, other: &Record) -> Option<Ordering> {
-        Some(self.timestamp.cmp(&other.timestamp))
+impl Record {
+    fn display_with_tz<'a>(&'a self, timezone: &'a TimeZoneSetting) -> RecordDisplay<'a> {
+        RecordDisplay {
+            record: self,
+            timezone,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TimeZoneSetting {
+    Local,
+    Named(Tz),
+}
+
+impl TimeZoneSetting {
+    fn format(&self, timestamp: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            TimeZoneSetting::Local => {
+                DateTime::<Local>::from(timestamp).format(fmt).to_string()
+            }
+            TimeZoneSetting::Named(tz) => timestamp.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+
+    fn date_naive(&self, timestamp: DateTime<Utc>) -> chrono::NaiveDate {
+        match self {
+            TimeZoneSetting::Local => DateTime::<Local>::from(timestamp).date_naive(),
+            TimeZoneSetting::Named(tz) => timestamp.with_timezone(tz).date_naive(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Config {
+    timezone: Option<String>,
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("config.toml")
+}
+
+fn load_config(data_dir: &Path) -> Result<Config> {
+    let path = config_path(data_dir);
+    if path.exists() {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    } else {
+        Ok(Config::default())
+    }
+}
+
+fn resolve_timezone(cli_timezone: &Option<String>, data_dir: &Path) -> Result<TimeZoneSetting> {
+    let name = match cli_timezone {
+        Some(name) => Some(name.clone()),
+        None => load_config(data_dir)?.timezone,
+    };
+    match name {
+        Some(name) => match name.parse::<Tz>() {
+            Ok(tz) => Ok(TimeZoneSetting::Named(tz)),
+            Err(err) => bail!("Unknown timezone '{}': {}", name, err),
+        },
+        None => Ok(TimeZoneSetting::Local),
     }
 }
 
@@ -46,10 +192,110 @@ struct RecordOpts {
     pulse: u32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl FromStr for Bucket {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "day" => Ok(Bucket::Day),
+            "week" => Ok(Bucket::Week),
+            "month" => Ok(Bucket::Month),
+            other => bail!("Unknown bucket '{}', expected day, week, or month", other),
+        }
+    }
+}
+
 #[derive(Debug, StructOpt)]
 struct ReportOpts {
     #[structopt(default_value = "10", long)]
     limit: usize,
+    #[structopt(long, help = "Only include records at or after this RFC3339 timestamp")]
+    start: Option<DateTime<Utc>>,
+    #[structopt(long, help = "Only include records at or before this RFC3339 timestamp")]
+    end: Option<DateTime<Utc>>,
+    #[structopt(
+        long,
+        help = "Group records into day/week/month buckets and report per-bucket averages"
+    )]
+    bucket: Option<Bucket>,
+    #[structopt(
+        long,
+        help = "Only include records classified in this AHA category (normal/elevated/stage1/stage2/crisis)"
+    )]
+    category: Option<Category>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ChartOpts {
+    #[structopt(long, help = "Only include records at or after this RFC3339 timestamp")]
+    start: Option<DateTime<Utc>>,
+    #[structopt(long, help = "Only include records at or before this RFC3339 timestamp")]
+    end: Option<DateTime<Utc>>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "Where to write the chart; defaults under the bloodpressure data directory"
+    )]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct ImportOpts {
+    #[structopt(parse(from_os_str), help = "Spreadsheet (.xlsx/.xls) or CSV file to import")]
+    input: PathBuf,
+    #[structopt(
+        long,
+        default_value = "0",
+        help = "Column index (0-based) containing the timestamp"
+    )]
+    timestamp_column: usize,
+    #[structopt(
+        long,
+        default_value = "1",
+        help = "Column index (0-based) containing the systolic pressure"
+    )]
+    systolic_column: usize,
+    #[structopt(
+        long,
+        default_value = "2",
+        help = "Column index (0-based) containing the diastolic pressure"
+    )]
+    diastolic_column: usize,
+    #[structopt(
+        long,
+        default_value = "3",
+        help = "Column index (0-based) containing the pulse"
+    )]
+    pulse_column: usize,
+    #[structopt(
+        long,
+        default_value = ",",
+        help = "Field delimiter for CSV input; ignored for spreadsheets"
+    )]
+    delimiter: char,
+    #[structopt(
+        long,
+        help = "chrono strftime format for parsing timestamps; defaults to RFC3339"
+    )]
+    date_format: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+enum ConfigCommand {
+    /// Persist a default timezone in config.toml so reports are reproducible regardless
+    /// of the machine's current locale; the global --timezone flag still overrides it
+    /// for a single invocation.
+    SetTimezone {
+        #[structopt(name = "timezone-name", help = "IANA timezone name (e.g. America/New_York)")]
+        tz: String,
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -57,26 +303,195 @@ struct ReportOpts {
 enum Command {
     Record(RecordOpts),
     Report(ReportOpts),
+    Chart(ChartOpts),
+    Import(ImportOpts),
+    /// One-shot migration of a legacy monolithic data.csv into monthly segments.
+    Migrate,
     ShowPath,
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "bloodpressure", about = "Record and report my blood pressure")]
+struct Opt {
+    #[structopt(subcommand)]
+    command: Command,
+    #[structopt(
+        long,
+        global = true,
+        help = "IANA timezone name (e.g. America/New_York) used for timestamping and display"
+    )]
+    timezone: Option<String>,
 }
 
-fn get_data_paths() -> Result<(PathBuf, PathBuf)> {
+fn get_data_dir() -> Result<PathBuf> {
     if let Some(p) = dirs::data_local_dir() {
-        let dd = p.join("bloodpressure");
-        let df = dd.join("data.csv");
-        Ok((dd, df))
+        Ok(p.join("bloodpressure"))
     } else {
         bail!("Could not compute path!");
     }
 }
 
-fn do_record(opts: RecordOpts) -> Result<()> {
-    let (data_dir, data_path) = get_data_paths()?;
-    fs::create_dir_all(data_dir)?;
-    let file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(data_path)?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    Month,
+}
+
+#[derive(Debug, Clone)]
+struct RollingFileConfig {
+    directory: PathBuf,
+    prefix: String,
+    granularity: Granularity,
+}
+
+impl RollingFileConfig {
+    fn new(directory: PathBuf) -> Self {
+        RollingFileConfig {
+            directory,
+            prefix: "data".to_string(),
+            granularity: Granularity::Month,
+        }
+    }
+
+    fn segment_start(&self, timestamp: DateTime<Utc>) -> chrono::NaiveDate {
+        match self.granularity {
+            Granularity::Month => timestamp.date_naive().with_day(1).unwrap(),
+        }
+    }
+
+    fn segment_name(&self, segment_start: chrono::NaiveDate) -> String {
+        match self.granularity {
+            Granularity::Month => format!(
+                "{}-{}.csv",
+                self.prefix,
+                segment_start.format("%Y-%m")
+            ),
+        }
+    }
+
+    fn segment_path(&self, timestamp: DateTime<Utc>) -> PathBuf {
+        self.directory
+            .join(self.segment_name(self.segment_start(timestamp)))
+    }
+
+    fn footer_path(&self, segment_path: &Path) -> PathBuf {
+        let mut name = segment_path.as_os_str().to_owned();
+        name.push(".count");
+        PathBuf::from(name)
+    }
+
+    /// Segments already on disk, oldest first, as (segment start date, path).
+    fn existing_segments(&self) -> Result<Vec<(chrono::NaiveDate, PathBuf)>> {
+        let mut segments = vec![];
+        if !self.directory.exists() {
+            return Ok(segments);
+        }
+        let suffix = ".csv";
+        let prefix = format!("{}-", self.prefix);
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(middle) = name.strip_prefix(&prefix).and_then(|n| n.strip_suffix(suffix))
+            else {
+                continue;
+            };
+            if let Ok(start) = chrono::NaiveDate::parse_from_str(&format!("{middle}-01"), "%Y-%m-%d")
+            {
+                segments.push((start, path));
+            }
+        }
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Segments overlapping `[start, end]`, oldest first.
+    fn segments_in_range(
+        &self,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .existing_segments()?
+            .into_iter()
+            .filter(|(segment_start, _)| {
+                let segment_end = *segment_start + chrono::Months::new(1);
+                let segment_start_ts =
+                    DateTime::<Utc>::from_naive_utc_and_offset(segment_start.and_time(chrono::NaiveTime::MIN), Utc);
+                let segment_end_ts =
+                    DateTime::<Utc>::from_naive_utc_and_offset(segment_end.and_time(chrono::NaiveTime::MIN), Utc);
+                start.is_none_or(|start| segment_end_ts > start)
+                    && end.is_none_or(|end| segment_start_ts <= end)
+            })
+            .map(|(_, path)| path)
+            .collect())
+    }
+}
+
+fn read_footer(footer_path: &Path) -> Result<Option<usize>> {
+    if !footer_path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(footer_path)?.trim().parse()?))
+}
+
+fn write_footer(footer_path: &Path, count: usize) -> Result<()> {
+    fs::write(footer_path, count.to_string())?;
+    Ok(())
+}
+
+fn read_segment(config: &RollingFileConfig, segment_path: &Path) -> Result<Vec<Record>> {
+    let file = fs::OpenOptions::new().read(true).open(segment_path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(file);
+    let mut records = vec![];
+    for result in reader.deserialize() {
+        records.push(result?);
+    }
+
+    if let Some(expected) = read_footer(&config.footer_path(segment_path))? {
+        if expected != records.len() {
+            eprintln!(
+                "Warning: segment {:?} expected {} row(s) but found {}; it may be truncated or corrupt",
+                segment_path,
+                expected,
+                records.len()
+            );
+        }
+    }
+
+    Ok(records)
+}
+
+fn read_segments(
+    config: &RollingFileConfig,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<Vec<Record>> {
+    let mut records = vec![];
+    for segment_path in config.segments_in_range(start, end)? {
+        records.extend(read_segment(config, &segment_path)?);
+    }
+    Ok(records)
+}
+
+/// Re-count the rows actually on disk for a segment and refresh its sidecar footer.
+fn refresh_footer(config: &RollingFileConfig, segment_path: &Path) -> Result<()> {
+    let file = fs::OpenOptions::new().read(true).open(segment_path)?;
+    let count = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(file)
+        .records()
+        .count();
+    write_footer(&config.footer_path(segment_path), count)
+}
+
+fn do_record(opts: RecordOpts, timezone: &TimeZoneSetting) -> Result<()> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)?;
+    let config = RollingFileConfig::new(data_dir);
 
     let record = Record {
         timestamp: chrono::Utc::now(),
@@ -85,47 +500,646 @@ fn do_record(opts: RecordOpts) -> Result<()> {
         pulse: opts.pulse,
     };
 
+    let segment_path = config.segment_path(record.timestamp);
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&segment_path)?;
     let mut writer = csv::WriterBuilder::new()
         .has_headers(false)
         .from_writer(file);
-    writer.serialize(record)?;
+    writer.serialize(&record)?;
     writer.flush()?;
+    drop(writer);
+    refresh_footer(&config, &segment_path)?;
+
+    println!("Recorded: {}", record.display_with_tz(timezone));
+    Ok(())
+}
+
+fn bucket_start(date: chrono::NaiveDate, bucket: Bucket) -> chrono::NaiveDate {
+    match bucket {
+        Bucket::Day => date,
+        Bucket::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Bucket::Month => date.with_day(1).unwrap(),
+    }
+}
+
+fn do_report(opts: ReportOpts, timezone: &TimeZoneSetting) -> Result<()> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)?;
+    let config = RollingFileConfig::new(data_dir);
+    let mut records = read_segments(&config, opts.start, opts.end)?;
+
+    records.retain(|record| {
+        opts.start.is_none_or(|start| record.timestamp >= start)
+            && opts.end.is_none_or(|end| record.timestamp <= end)
+    });
+    records.sort();
+    records.reverse();
+
+    let filtered_records: Vec<&Record> = records
+        .iter()
+        .filter(|record| {
+            opts.category
+                .is_none_or(|category| Category::classify(record.systolic, record.diastolic) == category)
+        })
+        .collect();
+
+    match opts.bucket {
+        Some(bucket) => {
+            let mut buckets: BTreeMap<chrono::NaiveDate, Vec<&Record>> = BTreeMap::new();
+            for &record in &filtered_records {
+                let key = bucket_start(timezone.date_naive(record.timestamp), bucket);
+                buckets.entry(key).or_default().push(record);
+            }
+            for (start, bucket_records) in buckets.iter().rev() {
+                let count = bucket_records.len() as f64;
+                let systolic: f64 =
+                    bucket_records.iter().map(|r| r.systolic as f64).sum::<f64>() / count;
+                let diastolic: f64 =
+                    bucket_records.iter().map(|r| r.diastolic as f64).sum::<f64>() / count;
+                let pulse: f64 = bucket_records.iter().map(|r| r.pulse as f64).sum::<f64>() / count;
+                println!(
+                    "{}\tBP: {:.1}/{:.1}\tPulse: {:.1}\tCount: {}",
+                    start,
+                    systolic,
+                    diastolic,
+                    pulse,
+                    bucket_records.len()
+                );
+            }
+        }
+        None => {
+            for record in filtered_records.iter().take(opts.limit) {
+                println!("{}", record.display_with_tz(timezone));
+            }
+        }
+    }
+
+    println!("\nCategories:");
+    for category in Category::ALL {
+        let count = records
+            .iter()
+            .filter(|record| Category::classify(record.systolic, record.diastolic) == category)
+            .count();
+        println!("  {}: {}", category, count);
+    }
+
     Ok(())
 }
 
-fn do_report(opts: ReportOpts) -> Result<()> {
-    let (data_dir, data_path) = get_data_paths()?;
-    fs::create_dir_all(data_dir)?;
-    let file = fs::OpenOptions::new().read(true).open(data_path)?;
+fn metric_trace(
+    timestamps: &[String],
+    values: &[u32],
+    name: &str,
+    color: palette::Srgb<u8>,
+) -> Box<plotly::Scatter<String, u32>> {
+    plotly::Scatter::new(timestamps.to_vec(), values.to_vec())
+        .mode(plotly::common::Mode::Lines)
+        .name(name)
+        .line(
+            plotly::common::Line::new()
+                .color(plotly::color::Rgb::new(color.red, color.green, color.blue)),
+        )
+}
+
+fn do_chart(opts: ChartOpts, timezone: &TimeZoneSetting) -> Result<()> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)?;
+    let config = RollingFileConfig::new(data_dir.clone());
+    let mut records = read_segments(&config, opts.start, opts.end)?;
+
+    records.retain(|record| {
+        opts.start.is_none_or(|start| record.timestamp >= start)
+            && opts.end.is_none_or(|end| record.timestamp <= end)
+    });
+    records.sort();
+
+    let timestamps: Vec<String> = records
+        .iter()
+        .map(|record| timezone.format(record.timestamp, "%Y-%m-%dT%H:%M:%S"))
+        .collect();
+    let systolic: Vec<u32> = records.iter().map(|record| record.systolic).collect();
+    let diastolic: Vec<u32> = records.iter().map(|record| record.diastolic).collect();
+    let pulse: Vec<u32> = records.iter().map(|record| record.pulse).collect();
+
+    let mut plot = plotly::Plot::new();
+    plot.add_trace(metric_trace(
+        &timestamps,
+        &systolic,
+        "Systolic",
+        palette::Srgb::new(220u8, 20, 60),
+    ));
+    plot.add_trace(metric_trace(
+        &timestamps,
+        &diastolic,
+        "Diastolic",
+        palette::Srgb::new(65u8, 105, 225),
+    ));
+    plot.add_trace(metric_trace(
+        &timestamps,
+        &pulse,
+        "Pulse",
+        palette::Srgb::new(46u8, 139, 87),
+    ));
+    plot.set_layout(
+        plotly::Layout::new()
+            .title(plotly::common::Title::new("Blood Pressure Trends"))
+            .x_axis(plotly::layout::Axis::new().title(plotly::common::Title::new("Date")))
+            .y_axis(plotly::layout::Axis::new().title(plotly::common::Title::new("mmHg / bpm"))),
+    );
+
+    let output = opts.output.unwrap_or_else(|| data_dir.join("chart.html"));
+    plot.write_html(&output);
+    println!("Wrote chart to {:?}", output);
+
+    Ok(())
+}
+
+/// Format used for spreadsheet date/time cells, which carry no RFC3339 timezone of their own.
+const SPREADSHEET_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn parse_timestamp(raw: &str, date_format: &Option<String>) -> Result<DateTime<Utc>> {
+    if let Some(fmt) = date_format {
+        let naive = chrono::NaiveDateTime::parse_from_str(raw, fmt)?;
+        return Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let naive = chrono::NaiveDateTime::parse_from_str(raw, SPREADSHEET_DATETIME_FORMAT)?;
+    Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+}
+
+fn row_to_record(row: &[String], opts: &ImportOpts) -> Result<Record> {
+    let column = |idx: usize| -> Result<&String> {
+        row.get(idx)
+            .ok_or_else(|| anyhow!("Row {:?} is missing column {}", row, idx))
+    };
+    Ok(Record {
+        timestamp: parse_timestamp(column(opts.timestamp_column)?, &opts.date_format)?,
+        systolic: column(opts.systolic_column)?.trim().parse()?,
+        diastolic: column(opts.diastolic_column)?.trim().parse()?,
+        pulse: column(opts.pulse_column)?.trim().parse()?,
+    })
+}
+
+fn read_csv_rows(path: &Path, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let file = fs::File::open(path)?;
     let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
         .has_headers(false)
         .from_reader(file);
-    let mut records: Vec<Record> = vec![];
+    let mut rows = vec![];
+    for result in reader.records() {
+        let record = result?;
+        rows.push(record.iter().map(|cell| cell.to_string()).collect());
+    }
+    Ok(rows)
+}
+
+/// Renders a spreadsheet cell as a string, special-casing native Excel date/time cells
+/// (stored as numeric serials) so they round-trip through `parse_timestamp` instead of
+/// being stringified as raw numbers.
+fn spreadsheet_cell_to_string(cell: &calamine::Data) -> String {
+    use calamine::DataType;
+
+    match cell.as_datetime() {
+        Some(datetime) => datetime.format(SPREADSHEET_DATETIME_FORMAT).to_string(),
+        None => cell.to_string(),
+    }
+}
+
+fn read_spreadsheet_rows(path: &Path) -> Result<Vec<Vec<String>>> {
+    use calamine::Reader;
+
+    let mut workbook = calamine::open_workbook_auto(path)?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| anyhow!("Spreadsheet {:?} has no sheets", path))??;
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(spreadsheet_cell_to_string).collect())
+        .collect())
+}
+
+fn do_import(opts: ImportOpts) -> Result<()> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)?;
+    let config = RollingFileConfig::new(data_dir);
+
+    let mut seen: std::collections::HashSet<DateTime<Utc>> = read_segments(&config, None, None)?
+        .into_iter()
+        .map(|record| record.timestamp)
+        .collect();
 
+    let extension = opts
+        .input
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let rows = if extension == "xlsx" || extension == "xls" {
+        read_spreadsheet_rows(&opts.input)?
+    } else {
+        read_csv_rows(&opts.input, opts.delimiter)?
+    };
+
+    let mut by_segment: BTreeMap<PathBuf, Vec<Record>> = BTreeMap::new();
+    let mut skipped = 0usize;
+    let mut invalid: Vec<(usize, anyhow::Error)> = vec![];
+    for (index, row) in rows.iter().enumerate() {
+        match row_to_record(row, &opts) {
+            Ok(record) => {
+                if seen.insert(record.timestamp) {
+                    by_segment
+                        .entry(config.segment_path(record.timestamp))
+                        .or_default()
+                        .push(record);
+                } else {
+                    skipped += 1;
+                }
+            }
+            Err(err) => invalid.push((index, err)),
+        }
+    }
+
+    let mut imported = 0usize;
+    for (segment_path, segment_records) in by_segment {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&segment_path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file);
+        for record in &segment_records {
+            writer.serialize(record)?;
+            imported += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        refresh_footer(&config, &segment_path)?;
+    }
+
+    for (index, err) in &invalid {
+        eprintln!("Warning: skipping row {} in {:?}: {}", index, opts.input, err);
+    }
+    println!(
+        "Imported {} new record(s) from {:?} ({} skipped as duplicates, {} skipped as invalid)",
+        imported,
+        opts.input,
+        skipped,
+        invalid.len()
+    );
+    Ok(())
+}
+
+fn do_migrate() -> Result<()> {
+    let data_dir = get_data_dir()?;
+    let legacy_path = data_dir.join("data.csv");
+    if !legacy_path.exists() {
+        println!(
+            "No legacy {:?} found; nothing to migrate",
+            legacy_path
+        );
+        return Ok(());
+    }
+
+    let file = fs::OpenOptions::new().read(true).open(&legacy_path)?;
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(file);
+    let mut records: Vec<Record> = vec![];
     for result in reader.deserialize() {
         records.push(result?);
     }
-    records.sort();
-    records.reverse();
 
-    for record in records.iter().take(opts.limit) {
-        println!("{}", record);
+    let config = RollingFileConfig::new(data_dir.clone());
+    let mut seen: std::collections::HashSet<DateTime<Utc>> = read_segments(&config, None, None)?
+        .into_iter()
+        .map(|record| record.timestamp)
+        .collect();
+
+    let mut by_segment: BTreeMap<PathBuf, Vec<Record>> = BTreeMap::new();
+    let mut skipped = 0usize;
+    for record in records {
+        if seen.insert(record.timestamp) {
+            by_segment
+                .entry(config.segment_path(record.timestamp))
+                .or_default()
+                .push(record);
+        } else {
+            skipped += 1;
+        }
+    }
+
+    let mut migrated = 0usize;
+    for (segment_path, mut segment_records) in by_segment {
+        segment_records.sort();
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&segment_path)?;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file);
+        for record in &segment_records {
+            writer.serialize(record)?;
+            migrated += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        refresh_footer(&config, &segment_path)?;
     }
 
+    let backup_path = data_dir.join("data.csv.migrated");
+    fs::rename(&legacy_path, &backup_path)?;
+    println!(
+        "Migrated {} record(s) ({} skipped as duplicates) from legacy {:?} into monthly segments; original preserved at {:?}",
+        migrated, skipped, legacy_path, backup_path
+    );
     Ok(())
 }
 
 fn do_show_path() -> Result<()> {
-    let (_, data_path) = get_data_paths()?;
-    println!("Data Path: {:?}", data_path);
+    let data_dir = get_data_dir()?;
+    println!(
+        "Data Dir: {:?} (segments named data-YYYY-MM.csv)",
+        data_dir
+    );
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let command = Command::from_args();
+fn do_config(command: ConfigCommand, data_dir: &Path) -> Result<()> {
     match command {
-        Command::Record(opts) => do_record(opts),
-        Command::Report(opts) => do_report(opts),
+        ConfigCommand::SetTimezone { tz } => {
+            if let Err(err) = tz.parse::<Tz>() {
+                bail!("Unknown timezone '{}': {}", tz, err);
+            }
+            fs::create_dir_all(data_dir)?;
+            let path = config_path(data_dir);
+            let config = Config {
+                timezone: Some(tz.clone()),
+            };
+            fs::write(&path, toml::to_string(&config)?)?;
+            println!("Saved default timezone '{}' to {:?}", tz, path);
+            Ok(())
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let data_dir = get_data_dir()?;
+    let timezone = resolve_timezone(&opt.timezone, &data_dir)?;
+    match opt.command {
+        Command::Record(opts) => do_record(opts, &timezone),
+        Command::Report(opts) => do_report(opts, &timezone),
+        Command::Chart(opts) => do_chart(opts, &timezone),
+        Command::Import(opts) => do_import(opts),
+        Command::Migrate => do_migrate(),
         Command::ShowPath => do_show_path(),
+        Command::Config(command) => do_config(command, &data_dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timestamp_uses_custom_format_else_rfc3339_then_spreadsheet_fallback() {
+        let expected = ts(2024, 1, 5, 8);
+
+        // No format given: parses as RFC3339.
+        assert_eq!(
+            parse_timestamp("2024-01-05T08:00:00Z", &None).unwrap(),
+            expected
+        );
+
+        // Format given: uses it instead of RFC3339, even though the raw string isn't RFC3339.
+        assert_eq!(
+            parse_timestamp("01/05/2024 08:00", &Some("%m/%d/%Y %H:%M".to_string())).unwrap(),
+            expected
+        );
+
+        // No format given, not RFC3339: falls back to the naive spreadsheet format produced
+        // by spreadsheet_cell_to_string for native Excel date/time cells.
+        assert_eq!(
+            parse_timestamp("2024-01-05T08:00:00", &None).unwrap(),
+            expected
+        );
+
+        // With no format given, garbage matches neither RFC3339 nor the spreadsheet format.
+        assert!(parse_timestamp("not a timestamp", &None).is_err());
+    }
+
+    #[test]
+    fn row_to_record_reports_missing_columns_without_panicking() {
+        let opts = ImportOpts {
+            input: PathBuf::from("in.csv"),
+            timestamp_column: 0,
+            systolic_column: 1,
+            diastolic_column: 2,
+            pulse_column: 3,
+            delimiter: ',',
+            date_format: None,
+        };
+
+        let short_row = vec!["2024-01-05T08:00:00Z".to_string(), "120".to_string()];
+        assert!(row_to_record(&short_row, &opts).is_err());
+
+        let full_row = vec![
+            "2024-01-05T08:00:00Z".to_string(),
+            "120".to_string(),
+            "80".to_string(),
+            "60".to_string(),
+        ];
+        let record = row_to_record(&full_row, &opts).unwrap();
+        assert_eq!(record.timestamp, ts(2024, 1, 5, 8));
+        assert_eq!(record.systolic, 120);
+        assert_eq!(record.diastolic, 80);
+        assert_eq!(record.pulse, 60);
+    }
+
+    #[test]
+    fn resolve_timezone_prefers_cli_over_config() {
+        let dir = TempDir::new("resolve-timezone-precedence");
+        fs::write(config_path(&dir.0), "timezone = \"America/Chicago\"").unwrap();
+
+        let resolved = resolve_timezone(&Some("Europe/London".to_string()), &dir.0).unwrap();
+        match resolved {
+            TimeZoneSetting::Named(tz) => assert_eq!(tz, chrono_tz::Europe::London),
+            TimeZoneSetting::Local => panic!("expected a named timezone"),
+        }
+
+        let resolved = resolve_timezone(&None, &dir.0).unwrap();
+        match resolved {
+            TimeZoneSetting::Named(tz) => assert_eq!(tz, chrono_tz::America::Chicago),
+            TimeZoneSetting::Local => panic!("expected a named timezone"),
+        }
+    }
+
+    #[test]
+    fn resolve_timezone_falls_back_to_local_with_no_cli_or_config() {
+        let dir = TempDir::new("resolve-timezone-default");
+        let resolved = resolve_timezone(&None, &dir.0).unwrap();
+        match resolved {
+            TimeZoneSetting::Local => {}
+            TimeZoneSetting::Named(_) => panic!("expected the Local fallback"),
+        }
+    }
+
+    #[test]
+    fn resolve_timezone_rejects_invalid_zone_names() {
+        let dir = TempDir::new("resolve-timezone-invalid");
+        assert!(resolve_timezone(&Some("Not/AZone".to_string()), &dir.0).is_err());
+    }
+
+    #[test]
+    fn bucket_start_snaps_to_bucket_boundaries() {
+        let monday = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let wednesday = chrono::NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let next_monday = chrono::NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+
+        // Day bucketing is the identity function.
+        assert_eq!(bucket_start(wednesday, Bucket::Day), wednesday);
+
+        // Week bucketing snaps back to Monday, including when already on one.
+        assert_eq!(bucket_start(wednesday, Bucket::Week), monday);
+        assert_eq!(bucket_start(monday, Bucket::Week), monday);
+        assert_eq!(bucket_start(next_monday, Bucket::Week), next_monday);
+
+        // Month bucketing snaps to the 1st, including on the last day of the year.
+        assert_eq!(bucket_start(wednesday, Bucket::Month), monday);
+        let new_years_eve = chrono::NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        assert_eq!(
+            bucket_start(new_years_eve, Bucket::Month),
+            chrono::NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn category_classify_boundaries() {
+        // Normal stays Normal right up to the Elevated threshold.
+        assert_eq!(Category::classify(119, 79), Category::Normal);
+        // Elevated requires systolic 120-129 AND diastolic < 80.
+        assert_eq!(Category::classify(120, 79), Category::Elevated);
+        assert_eq!(Category::classify(129, 79), Category::Elevated);
+        // Diastolic 80 alone is enough to cross into Stage 1, regardless of systolic.
+        assert_eq!(Category::classify(119, 80), Category::Stage1);
+        assert_eq!(Category::classify(130, 79), Category::Stage1);
+        // Stage 2 kicks in at 140 systolic or 90 diastolic.
+        assert_eq!(Category::classify(140, 79), Category::Stage2);
+        assert_eq!(Category::classify(119, 90), Category::Stage2);
+        // Crisis requires strictly greater than 180/120, not >=.
+        assert_eq!(Category::classify(180, 120), Category::Stage2);
+        assert_eq!(Category::classify(181, 120), Category::Crisis);
+        assert_eq!(Category::classify(180, 121), Category::Crisis);
+    }
+
+    /// A scratch directory that's removed when the test finishes.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "bloodpressure-test-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn ts(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        DateTime::<Utc>::from_naive_utc_and_offset(
+            chrono::NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap(),
+            Utc,
+        )
+    }
+
+    #[test]
+    fn segments_in_range_includes_only_overlapping_months() {
+        let dir = TempDir::new("segments-in-range");
+        let config = RollingFileConfig::new(dir.0.clone());
+        for timestamp in [ts(2024, 1, 15, 0), ts(2024, 2, 1, 0), ts(2024, 3, 31, 23)] {
+            fs::write(config.segment_path(timestamp), "").unwrap();
+        }
+
+        // A record exactly at a month edge (2024-02-01T00:00:00) belongs to February,
+        // not January, even though January's segment ends at that same instant.
+        let segments = config
+            .segments_in_range(Some(ts(2024, 2, 1, 0)), Some(ts(2024, 2, 1, 0)))
+            .unwrap();
+        assert_eq!(segments, vec![config.segment_path(ts(2024, 2, 1, 0))]);
+
+        // A start late in January and an end exactly at the start of March include
+        // January and March as well as February.
+        let segments = config
+            .segments_in_range(Some(ts(2024, 1, 31, 23)), Some(ts(2024, 3, 1, 0)))
+            .unwrap();
+        assert_eq!(
+            segments,
+            vec![
+                config.segment_path(ts(2024, 1, 15, 0)),
+                config.segment_path(ts(2024, 2, 1, 0)),
+                config.segment_path(ts(2024, 3, 31, 23)),
+            ]
+        );
+
+        // A range entirely before the first segment matches nothing.
+        let segments = config
+            .segments_in_range(Some(ts(2023, 1, 1, 0)), Some(ts(2023, 12, 31, 23)))
+            .unwrap();
+        assert!(segments.is_empty());
+    }
+
+    #[test]
+    fn read_segment_warns_but_still_returns_records_on_footer_mismatch() {
+        let dir = TempDir::new("footer-mismatch");
+        let config = RollingFileConfig::new(dir.0.clone());
+        let segment_path = config.segment_path(ts(2024, 1, 1, 0));
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&segment_path)
+            .unwrap();
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file);
+        for timestamp in [ts(2024, 1, 1, 8), ts(2024, 1, 2, 8)] {
+            writer
+                .serialize(Record {
+                    timestamp,
+                    systolic: 120,
+                    diastolic: 80,
+                    pulse: 60,
+                })
+                .unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        // A footer that disagrees with the actual row count signals a truncated/corrupt
+        // segment; read_segment should still return whatever rows are actually on disk.
+        write_footer(&config.footer_path(&segment_path), 3).unwrap();
+
+        let records = read_segment(&config, &segment_path).unwrap();
+        assert_eq!(records.len(), 2);
     }
 }